@@ -0,0 +1,177 @@
+//! Batch resolution of Bank of Canada fixings for an arbitrary set of transaction dates.
+//!
+//! Reads a file of dates (optionally paired with an amount to convert) and resolves each to its
+//! business-day fixing, collapsing the many individual lookups into a single Valet query by
+//! computing the overall min/max span up front.
+
+use crate::{Cli, Observation, Roll, calendar, fetch_with_cache};
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use std::fs;
+use std::path::Path;
+
+/// One resolved row of batch output.
+pub struct BatchRow {
+    pub date: Date,
+    pub resolved_fixing_date: Date,
+    pub rate: Decimal,
+    pub converted_amount: Option<Decimal>,
+}
+
+/// One parsed line of the dates file: a transaction date and an optional amount to convert.
+struct BatchEntry {
+    date: Date,
+    amount: Option<Decimal>,
+}
+
+/// Resolves every date in `path` to its Bank of Canada fixing, using `args` for direction and
+/// roll convention.
+pub fn resolve_batch(args: &Cli, path: &Path) -> Result<Vec<BatchRow>, String> {
+    let entries = parse_entries(path)?;
+    let (Some(&min_date), Some(&max_date)) = (
+        entries.iter().map(|e| &e.date).min(),
+        entries.iter().map(|e| &e.date).max(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    // Pad a business day past each end so every entry's roll direction has somewhere to land,
+    // regardless of where it falls relative to the rest of the batch.
+    let fetch_start = calendar::previous_business_day(min_date);
+    let fetch_end = calendar::next_business_day(max_date);
+    let observations = fetch_with_cache(args.reverse, args.no_cache, fetch_start, fetch_end)?;
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let resolved = resolve_one(&observations, entry.date, args.roll)?;
+            Ok(BatchRow {
+                date: entry.date,
+                resolved_fixing_date: resolved.d,
+                rate: resolved.fx.v,
+                converted_amount: entry.amount.map(|amount| amount * resolved.fx.v),
+            })
+        })
+        .collect()
+}
+
+/// Picks the observation `date` resolves to under `roll`.
+pub(crate) fn resolve_one(observations: &[Observation], date: Date, roll: Roll) -> Result<&Observation, String> {
+    match roll {
+        Roll::Backward => observations.iter().filter(|o| o.d <= date).max_by_key(|o| o.d),
+        Roll::Forward => observations.iter().filter(|o| o.d >= date).min_by_key(|o| o.d),
+        Roll::Raise => observations.iter().find(|o| o.d == date),
+    }
+    .ok_or_else(|| format!("no Bank of Canada fixing available for {date}"))
+}
+
+/// Parses a newline- or CSV-delimited dates file into `(date, amount)` entries. Each line is a
+/// date, optionally followed by a comma and an amount to convert; any columns after that (a
+/// brokerage export's description, currency, memo, etc.) are ignored. A header row is tolerated,
+/// but only when its date column is the literal word `date` (case-insensitive) — anything else
+/// that fails to parse as a date is a malformed transaction row, not a header, and must error out
+/// rather than silently vanish.
+fn parse_entries(path: &Path) -> Result<Vec<BatchEntry>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+
+    let mut lines: Vec<&str> = contents.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    if lines.first().is_some_and(|line| first_field(line).eq_ignore_ascii_case("date")) {
+        lines.remove(0);
+    }
+
+    lines.into_iter().map(parse_entry).collect()
+}
+
+fn first_field(line: &str) -> &str {
+    line.split(',').next().unwrap().trim()
+}
+
+fn parse_entry(line: &str) -> Result<BatchEntry, String> {
+    let mut fields = line.split(',');
+    let date = fields
+        .next()
+        .unwrap()
+        .trim()
+        .parse::<Date>()
+        .map_err(|e| format!("invalid date in line {line:?}: {e}"))?;
+    let amount = fields
+        .next()
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            field
+                .parse::<Decimal>()
+                .map_err(|e| format!("invalid amount in line {line:?}: {e}"))
+        })
+        .transpose()?;
+    Ok(BatchEntry { date, amount })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    fn write_temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_entries_skips_header_row_and_ignores_trailing_columns() {
+        let path = write_temp_file(
+            "boc_usd_cad_test_parse_entries_header.csv",
+            "date,amount,description,currency\n2025-01-15,100.00,Sold ETF,USD\n2025-01-16,,Dividend,USD\n",
+        );
+        let entries = parse_entries(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].date, date(2025, 1, 15));
+        assert_eq!(entries[0].amount, Some("100.00".parse().unwrap()));
+        assert_eq!(entries[1].date, date(2025, 1, 16));
+        assert_eq!(entries[1].amount, None);
+    }
+
+    #[test]
+    fn parse_entries_rejects_malformed_first_line_instead_of_treating_it_as_a_header() {
+        let path = write_temp_file(
+            "boc_usd_cad_test_parse_entries_malformed_first_line.csv",
+            "2025-13-45,100.00\n2025-01-16,50.00\n",
+        );
+        let result = parse_entries(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_entries_without_header_still_works() {
+        let path = write_temp_file("boc_usd_cad_test_parse_entries_no_header.csv", "2025-01-15,100.00\n");
+        let entries = parse_entries(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].date, date(2025, 1, 15));
+    }
+
+    #[test]
+    fn resolve_one_applies_each_roll_direction() {
+        let observations = vec![
+            Observation::from_rate(date(2025, 1, 14), "1.30".parse().unwrap()),
+            Observation::from_rate(date(2025, 1, 16), "1.32".parse().unwrap()),
+        ];
+
+        assert_eq!(
+            resolve_one(&observations, date(2025, 1, 15), Roll::Backward).unwrap().d,
+            date(2025, 1, 14)
+        );
+        assert_eq!(
+            resolve_one(&observations, date(2025, 1, 15), Roll::Forward).unwrap().d,
+            date(2025, 1, 16)
+        );
+        assert!(resolve_one(&observations, date(2025, 1, 15), Roll::Raise).is_err());
+    }
+}
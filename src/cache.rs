@@ -0,0 +1,167 @@
+//! Local on-disk cache of previously-fetched Valet observations.
+//!
+//! Keyed by currency direction and date, so repeated invocations over overlapping date ranges
+//! don't need to hit the Bank of Canada again for days we already know the fixing for. Mirrors
+//! the caching approach used for currency rates in the `investments` crate: never trust the most
+//! recent day as final, since the Bank can still republish a correction for it later.
+
+use crate::Observation;
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// A single cached fixing, independent of the `Observation` wire format so the cache file isn't
+/// coupled to the Valet response shape.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct CachedRate {
+    d: Date,
+    v: Decimal,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    #[serde(default)]
+    usd_cad: Vec<CachedRate>,
+    #[serde(default)]
+    cad_usd: Vec<CachedRate>,
+}
+
+pub struct Cache {
+    path: Option<PathBuf>,
+    file: CacheFile,
+}
+
+impl Cache {
+    /// Loads the cache from the user's cache directory, or starts empty if it doesn't exist yet
+    /// or no cache directory is available on this platform.
+    pub fn load() -> Cache {
+        let path = cache_path();
+        let file = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Cache { path, file }
+    }
+
+    /// Returns the cached rate for `date` in the given direction, if present.
+    fn get(&self, reverse: bool, date: Date) -> Option<Decimal> {
+        self.rates(reverse)
+            .iter()
+            .find(|rate| rate.d == date)
+            .map(|rate| rate.v)
+    }
+
+    /// Looks up every business day in `days`, returning the cached observations found and the
+    /// subset of days that still need to be fetched.
+    pub fn satisfy(&self, reverse: bool, days: &[Date], last_confirmed: Date) -> (Vec<Observation>, Vec<Date>) {
+        let mut hits = Vec::new();
+        let mut misses = Vec::new();
+        for &day in days {
+            // Never trust today's (or a future) fixing from the cache: the Bank can still
+            // republish a correction for it later in the day.
+            match self.get(reverse, day).filter(|_| day <= last_confirmed) {
+                Some(v) => hits.push(Observation::from_rate(day, v)),
+                None => misses.push(day),
+            }
+        }
+        (hits, misses)
+    }
+
+    /// Merges newly-fetched observations into the cache, skipping any day newer than
+    /// `last_confirmed`, and persists the result to disk.
+    pub fn store(&mut self, reverse: bool, observations: &[Observation], last_confirmed: Date) {
+        let rates = self.rates_mut(reverse);
+        for obs in observations {
+            if obs.d > last_confirmed {
+                continue;
+            }
+            match rates.iter_mut().find(|rate| rate.d == obs.d) {
+                Some(rate) => rate.v = obs.fx.v,
+                None => rates.push(CachedRate { d: obs.d, v: obs.fx.v }),
+            }
+        }
+        rates.sort_unstable_by_key(|rate| rate.d);
+        self.save();
+    }
+
+    fn rates(&self, reverse: bool) -> &Vec<CachedRate> {
+        if reverse { &self.file.cad_usd } else { &self.file.usd_cad }
+    }
+
+    fn rates_mut(&mut self, reverse: bool) -> &mut Vec<CachedRate> {
+        if reverse {
+            &mut self.file.cad_usd
+        } else {
+            &mut self.file.usd_cad
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(contents) = serde_json::to_string_pretty(&self.file) {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+fn cache_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "boc_usd_cad")
+        .map(|dirs| dirs.cache_dir().join("observations.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    /// Builds an in-memory cache (no `path`, so `store` never touches disk) seeded with the given
+    /// USD->CAD rates.
+    fn cache_with(rates: Vec<(Date, &str)>) -> Cache {
+        let usd_cad = rates
+            .into_iter()
+            .map(|(d, v)| CachedRate { d, v: v.parse().unwrap() })
+            .collect();
+        Cache {
+            path: None,
+            file: CacheFile { usd_cad, cad_usd: Vec::new() },
+        }
+    }
+
+    #[test]
+    fn satisfy_treats_last_confirmed_as_inclusive_boundary() {
+        let cache = cache_with(vec![(date(2025, 1, 14), "1.30"), (date(2025, 1, 15), "1.31")]);
+        let (hits, misses) = cache.satisfy(
+            false,
+            &[date(2025, 1, 14), date(2025, 1, 15)],
+            date(2025, 1, 14),
+        );
+        assert_eq!(hits.iter().map(|obs| obs.d).collect::<Vec<_>>(), vec![date(2025, 1, 14)]);
+        assert_eq!(misses, vec![date(2025, 1, 15)]);
+    }
+
+    #[test]
+    fn store_skips_dates_after_last_confirmed() {
+        let mut cache = cache_with(vec![]);
+        let observations = vec![
+            Observation::from_rate(date(2025, 1, 14), "1.30".parse().unwrap()),
+            Observation::from_rate(date(2025, 1, 15), "1.31".parse().unwrap()),
+        ];
+        cache.store(false, &observations, date(2025, 1, 14));
+        assert_eq!(cache.get(false, date(2025, 1, 14)), Some("1.30".parse().unwrap()));
+        assert_eq!(cache.get(false, date(2025, 1, 15)), None);
+    }
+
+    #[test]
+    fn store_overwrites_existing_rate_for_the_same_date() {
+        let mut cache = cache_with(vec![(date(2025, 1, 14), "1.30")]);
+        let observations = vec![Observation::from_rate(date(2025, 1, 14), "1.35".parse().unwrap())];
+        cache.store(false, &observations, date(2025, 1, 14));
+        assert_eq!(cache.get(false, date(2025, 1, 14)), Some("1.35".parse().unwrap()));
+    }
+}
@@ -0,0 +1,160 @@
+//! Offline calendar of Bank of Canada business days.
+//!
+//! The Valet API only has data for days the Bank actually published a fixing, so knowing which
+//! days those are ahead of time lets us resolve a requested date to its fixing without guessing
+//! how far back (or forward) to search.
+
+use jiff::ToSpan;
+use jiff::civil::{Date, Weekday, date};
+
+/// Returns `true` if `d` is a day the Bank of Canada publishes a noon/closing exchange rate on,
+/// i.e. not a weekend and not one of its observed holidays.
+pub fn is_business_day(d: Date) -> bool {
+    !matches!(d.weekday(), Weekday::Saturday | Weekday::Sunday) && !holidays(d.year()).contains(&d)
+}
+
+/// Returns the closest business day on or before `d`.
+pub fn previous_business_day(d: Date) -> Date {
+    let mut day = d;
+    while !is_business_day(day) {
+        day -= 1.day();
+    }
+    day
+}
+
+/// Returns the closest business day on or after `d`.
+pub fn next_business_day(d: Date) -> Date {
+    let mut day = d;
+    while !is_business_day(day) {
+        day += 1.day();
+    }
+    day
+}
+
+/// Returns every business day in the inclusive range `start..=end`.
+pub fn business_days_between(start: Date, end: Date) -> Vec<Date> {
+    let mut days = Vec::new();
+    let mut day = start;
+    while day <= end {
+        if is_business_day(day) {
+            days.push(day);
+        }
+        day += 1.day();
+    }
+    days
+}
+
+/// The Bank of Canada's observed holidays for `year`, including weekend rolls.
+///
+/// Source: <https://www.bankofcanada.ca/about/contact-information/bank-of-canada-holiday-schedule/>
+fn holidays(year: i16) -> Vec<Date> {
+    let easter = easter_sunday(year);
+    let good_friday = easter - 2.days();
+    let easter_monday = easter + 1.day();
+    let victoria_day = monday_on_or_before(date(year, 5, 24));
+    let civic_holiday = first_monday(year, 8);
+    let labour_day = first_monday(year, 9);
+    let thanksgiving = first_monday(year, 10) + 7.days();
+
+    // Christmas and Boxing Day are rolled together: if Christmas falls on a weekend it bumps
+    // Boxing Day out of the way rather than the two colliding on the same observed day.
+    let (christmas, boxing_day) = match date(year, 12, 25).weekday() {
+        Weekday::Saturday => (date(year, 12, 27), date(year, 12, 28)),
+        Weekday::Sunday => (date(year, 12, 26), date(year, 12, 27)),
+        _ => (date(year, 12, 25), roll_weekend_to_monday(date(year, 12, 26))),
+    };
+
+    vec![
+        roll_weekend_to_monday(date(year, 1, 1)),
+        good_friday,
+        easter_monday,
+        victoria_day,
+        roll_weekend_to_monday(date(year, 7, 1)),
+        civic_holiday,
+        labour_day,
+        thanksgiving,
+        christmas,
+        boxing_day,
+    ]
+}
+
+/// Moves a fixed-date holiday that falls on a weekend to the following Monday.
+fn roll_weekend_to_monday(d: Date) -> Date {
+    match d.weekday() {
+        Weekday::Saturday => d + 2.days(),
+        Weekday::Sunday => d + 1.day(),
+        _ => d,
+    }
+}
+
+fn monday_on_or_before(d: Date) -> Date {
+    let mut day = d;
+    while day.weekday() != Weekday::Monday {
+        day -= 1.day();
+    }
+    day
+}
+
+fn first_monday(year: i16, month: i8) -> Date {
+    let mut day = date(year, month, 1);
+    while day.weekday() != Weekday::Monday {
+        day += 1.day();
+    }
+    day
+}
+
+/// Computes the date of Easter Sunday using the Anonymous Gregorian (Meeus/Jones/Butcher)
+/// algorithm.
+fn easter_sunday(year: i16) -> Date {
+    let year = year as i32;
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = ((h + l - 7 * m + 114) % 31) + 1;
+    date(year as i16, month as i8, day as i8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+        assert_eq!(easter_sunday(2000), date(2000, 4, 23));
+    }
+
+    #[test]
+    fn test_fixed_holiday_weekend_roll() {
+        // Canada Day 2023 fell on a Saturday, observed the following Monday.
+        assert!(!is_business_day(date(2023, 7, 1)));
+        assert!(!is_business_day(date(2023, 7, 3)));
+        assert!(is_business_day(date(2023, 7, 4)));
+    }
+
+    #[test]
+    fn test_christmas_boxing_day_collision() {
+        // Christmas 2021 fell on a Saturday: observed Monday Dec 27, pushing Boxing Day to Tue Dec 28.
+        assert!(!is_business_day(date(2021, 12, 27)));
+        assert!(!is_business_day(date(2021, 12, 28)));
+        assert!(is_business_day(date(2021, 12, 29)));
+    }
+
+    #[test]
+    fn test_previous_business_day_over_long_weekend() {
+        // 2025-01-18/19 is a weekend.
+        assert_eq!(previous_business_day(date(2025, 1, 18)), date(2025, 1, 17));
+        assert_eq!(previous_business_day(date(2025, 1, 19)), date(2025, 1, 17));
+    }
+}
@@ -1,3 +1,9 @@
+pub mod batch;
+mod cache;
+mod calendar;
+pub mod output;
+pub mod recurrence;
+
 use clap::Parser;
 use jiff::ToSpan;
 use jiff::civil::Date;
@@ -5,6 +11,7 @@ use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde_json::Value;
 use std::cmp::Ordering;
+use std::path::PathBuf;
 
 const BOC_BASE_URL: &str = "https://www.bankofcanada.ca/valet";
 
@@ -14,9 +21,10 @@ const BOC_BASE_URL: &str = "https://www.bankofcanada.ca/valet";
 /// business day if selected date is not available.
 #[derive(Parser)]
 pub struct Cli {
-    /// A single date, or start date of the range (format: YYYY-MM-DD)
-    #[arg(value_name = "DATE")]
-    pub start_date: Date,
+    /// A single date, start date of the range, or anchor date for `--rrule` (format:
+    /// YYYY-MM-DD). Not required when `--dates-file` is given.
+    #[arg(value_name = "DATE", required_unless_present = "dates_file")]
+    pub start_date: Option<Date>,
     /// End date of the range (format: YYYY-MM-DD)
     #[arg(value_name = "DATE")]
     pub end_date: Option<Date>,
@@ -24,42 +32,139 @@ pub struct Cli {
     /// Provide the exchange rate from CAD to USD
     #[clap(short, long)]
     pub reverse: bool,
+
+    /// How to resolve a requested date that is not a Bank of Canada business day
+    #[clap(long, value_enum, default_value_t = Roll::Backward)]
+    pub roll: Roll,
+
+    /// Bypass the local cache and always fetch from the Bank of Canada
+    #[clap(long)]
+    pub no_cache: bool,
+
+    /// Resolve a batch of transaction dates (optionally with an amount column) from a file
+    /// instead of a single date or range
+    #[clap(long, value_name = "PATH")]
+    pub dates_file: Option<PathBuf>,
+
+    /// Output format
+    #[clap(long, value_enum, default_value_t = output::OutputFormat::Text)]
+    pub output: output::OutputFormat,
+
+    /// Generate dates from an RRULE-style recurrence expression (e.g.
+    /// `FREQ=MONTHLY;INTERVAL=1;COUNT=12`) instead of a single date, range, or `--dates-file`
+    #[clap(long, value_name = "RRULE", conflicts_with = "dates_file")]
+    pub rrule: Option<String>,
+}
+
+/// Convention for resolving a date that falls outside the Bank of Canada's published fixings.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Roll {
+    /// Use the nearest earlier business day's fixing
+    Backward,
+    /// Use the nearest later business day's fixing
+    Forward,
+    /// Return an error instead of substituting a different day
+    Raise,
+}
+
+impl std::fmt::Display for Roll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Roll::Backward => "backward",
+            Roll::Forward => "forward",
+            Roll::Raise => "raise",
+        })
+    }
 }
 
 pub fn retrieve_rates(args: &Cli) -> Result<Vec<Observation>, String> {
-    let request_url = match args.reverse {
-        false => format!("{BOC_BASE_URL}/observations/FXUSDCAD/json"),
-        true => format!("{BOC_BASE_URL}/observations/FXCADUSD/json"),
-    };
+    let start_date = args
+        .start_date
+        .expect("start_date is required when --dates-file is not given");
 
-    let request = ureq::get(request_url)
-        // Retrieve previous 10 days to account for weekends and holidays
-        .query("start_date", (args.start_date - 10.days()).to_string());
+    // The calendar tells us exactly which business days we expect fixings for, so the query
+    // only needs to cover that span instead of blindly widening by a fixed number of days.
+    let expected_start = resolve_roll(start_date, args.roll)?;
 
-    let request = match args.end_date {
-        None => request,
+    let expected_end = match args.end_date {
+        None => None,
         Some(end_date) => {
-            if end_date < args.start_date {
-                panic!(
-                    "end date {end_date} is before start date {}",
-                    args.start_date
-                );
-            } else {
-                request.query("end_date", end_date.to_string())
+            if end_date < start_date {
+                panic!("end date {end_date} is before start date {start_date}");
             }
+            Some(resolve_roll(end_date, args.roll)?)
+        }
+    };
+
+    let observations = fetch_with_cache(
+        args.reverse,
+        args.no_cache,
+        expected_start,
+        expected_end.unwrap_or(expected_start),
+    )?;
+
+    filter_rates(
+        start_date,
+        expected_start,
+        expected_end.is_some(),
+        args.roll,
+        observations,
+    )
+}
+
+/// Fetches observations for `reverse`'s direction over the inclusive range `start..=end`, routed
+/// through the local cache unless `no_cache` is set. Shared by single/range mode, batch mode, and
+/// recurrence mode so `--no-cache` and repeated-invocation caching behave the same everywhere.
+pub(crate) fn fetch_with_cache(reverse: bool, no_cache: bool, start: Date, end: Date) -> Result<Vec<Observation>, String> {
+    let required_days = calendar::business_days_between(start, end);
+
+    // Never trust a cached fixing for today (or later): the Bank can still republish a
+    // correction for it later in the day.
+    let today = jiff::Zoned::now().date();
+    let last_confirmed = calendar::previous_business_day(today - 1.day());
+
+    let mut cache = (!no_cache).then(cache::Cache::load);
+    let (mut observations, missing_days) = match &cache {
+        Some(cache) => cache.satisfy(reverse, &required_days, last_confirmed),
+        None => (Vec::new(), required_days),
+    };
+
+    if let Some((&fetch_start, &fetch_end)) = missing_days.first().zip(missing_days.last()) {
+        // The missing days might not be contiguous, but fetching their minimal spanning range is
+        // still a single Valet query; any already-cached days it happens to re-cover are deduped.
+        let fetched = fetch_observations(reverse, fetch_start, fetch_end)?;
+        if let Some(cache) = &mut cache {
+            cache.store(reverse, &fetched, last_confirmed);
         }
+        let new_observations: Vec<Observation> = fetched
+            .into_iter()
+            .filter(|obs| !observations.iter().any(|cached| cached.d == obs.d))
+            .collect();
+        observations.extend(new_observations);
+    }
+
+    Ok(observations)
+}
+
+/// Fetches observations for `reverse`'s direction over the inclusive range `start..=end` from the
+/// Valet API.
+pub(crate) fn fetch_observations(reverse: bool, start: Date, end: Date) -> Result<Vec<Observation>, String> {
+    let request_url = match reverse {
+        false => format!("{BOC_BASE_URL}/observations/FXUSDCAD/json"),
+        true => format!("{BOC_BASE_URL}/observations/FXCADUSD/json"),
     };
 
+    let request = ureq::get(request_url)
+        .query("start_date", start.to_string())
+        .query("end_date", end.to_string());
+
     let mut resp = request.call().expect("failure while accessing BoC Valet");
     if resp.status().is_success() {
-        Ok(filter_rates(
-            args.start_date,
-            args.end_date.is_some(),
-            resp.body_mut()
-                .read_json::<ObservationsResponse>()
-                .expect("failed to parse exchange data")
-                .observations,
-        ))
+        Ok(resp
+            .body_mut()
+            .read_json::<ObservationsResponse>()
+            .expect("failed to parse exchange data")
+            .observations)
     } else {
         Err(serde_json::to_string_pretty(
             &resp
@@ -72,27 +177,76 @@ pub fn retrieve_rates(args: &Cli) -> Result<Vec<Observation>, String> {
     }
 }
 
+/// Resolves a requested date to a Bank of Canada business day according to `roll`.
+fn resolve_roll(d: Date, roll: Roll) -> Result<Date, String> {
+    match roll {
+        Roll::Backward => Ok(calendar::previous_business_day(d)),
+        Roll::Forward => Ok(calendar::next_business_day(d)),
+        Roll::Raise => {
+            if calendar::is_business_day(d) {
+                Ok(d)
+            } else {
+                Err(format!("{d} is not a Bank of Canada business day"))
+            }
+        }
+    }
+}
+
 /// Filter rates to the selected date range
 fn filter_rates(
     start_date: Date,
+    expected_start: Date,
     is_range: bool,
+    roll: Roll,
     mut observations: Vec<Observation>,
-) -> Vec<Observation> {
-    // Index of the start date, defined as the specified start date, or the last day before it with data
+) -> Result<Vec<Observation>, String> {
+    // Index of the start date, defined as the specified start date, or the nearest day with data
+    // in the direction `roll` points.
     observations.sort_unstable();
-    let range_start = observations
-        .iter()
-        .enumerate()
-        .filter(|(_, obs)| obs.d <= start_date)
-        .max_by_key(|(_, obs)| obs.d)
-        .unwrap()
-        .0;
+    let range_start = match roll {
+        Roll::Backward | Roll::Raise => observations
+            .iter()
+            .enumerate()
+            .filter(|(_, obs)| obs.d <= start_date)
+            .max_by_key(|(_, obs)| obs.d)
+            .unwrap()
+            .0,
+        Roll::Forward => observations
+            .iter()
+            .enumerate()
+            .filter(|(_, obs)| obs.d >= start_date)
+            .min_by_key(|(_, obs)| obs.d)
+            .unwrap()
+            .0,
+    };
 
-    if is_range {
+    // The calendar should always land exactly on the fixing date the API returns; if it instead
+    // skipped further in the roll direction, the calendar is missing an unscheduled closure
+    // rather than our query being wrong, so warn instead of failing outright. Any divergence in
+    // the other direction means the calendar marked a real Bank of Canada business day as closed
+    // (the holiday table is a hand-maintained approximation and can fall out of date), so that's a
+    // clean error rather than a crash — same contract `raise` already holds unconditionally.
+    let resolved_date = observations[range_start].d;
+    match (roll, resolved_date.cmp(&expected_start)) {
+        (_, Ordering::Equal) => {}
+        (Roll::Backward, Ordering::Less) | (Roll::Forward, Ordering::Greater) => eprintln!(
+            "warning: Bank of Canada has no fixing on {expected_start}, further than the calendar expected; using {resolved_date} instead"
+        ),
+        (Roll::Raise, _) => {
+            return Err(format!("no Bank of Canada fixing available for {expected_start}"));
+        }
+        _ => {
+            return Err(format!(
+                "resolved fixing date {resolved_date} does not match the calendar's expected business day {expected_start}"
+            ));
+        }
+    }
+
+    Ok(if is_range {
         observations.into_iter().skip(range_start).collect()
     } else {
         vec![observations.into_iter().nth(range_start).unwrap()]
-    }
+    })
 }
 
 #[derive(Deserialize)]
@@ -107,6 +261,13 @@ pub struct Observation {
     pub fx: Fx,
 }
 
+impl Observation {
+    /// Builds an `Observation` from a cached fixing.
+    pub(crate) fn from_rate(d: Date, v: Decimal) -> Observation {
+        Observation { d, fx: Fx { v } }
+    }
+}
+
 impl PartialEq<Self> for Observation {
     fn eq(&self, other: &Self) -> bool {
         self.d.eq(&other.d)
@@ -135,9 +296,34 @@ pub struct Fx {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Cli, retrieve_rates};
+    use crate::{Cli, Observation, Roll, filter_rates, output, retrieve_rates};
     use jiff::civil::{Date, date};
 
+    /// `raise` must return an `Err` instead of panicking when the Bank's data skips past the
+    /// calendar's expected business day (e.g. an unscheduled closure the offline calendar doesn't
+    /// know about), rather than only handling the common weekend/known-holiday case.
+    #[test]
+    fn test_filter_rates_raise_errors_on_calendar_divergence() {
+        let observations = vec![
+            Observation::from_rate(date(2025, 1, 14), "1.0".parse().unwrap()),
+            Observation::from_rate(date(2025, 1, 16), "1.0".parse().unwrap()),
+        ];
+        let result = filter_rates(date(2025, 1, 15), date(2025, 1, 15), false, Roll::Raise, observations);
+        assert!(result.is_err());
+    }
+
+    /// `backward`/`forward` must also return an `Err`, not panic, when the Bank's data skips
+    /// further past the calendar's expected business day than the holiday table accounted for.
+    #[test]
+    fn test_filter_rates_backward_errors_on_calendar_divergence() {
+        let observations = vec![
+            Observation::from_rate(date(2025, 1, 14), "1.0".parse().unwrap()),
+            Observation::from_rate(date(2025, 1, 16), "1.0".parse().unwrap()),
+        ];
+        let result = filter_rates(date(2025, 1, 16), date(2025, 1, 15), false, Roll::Backward, observations);
+        assert!(result.is_err());
+    }
+
     /// Test that the correct dates are returned for a series of inputs.
     #[test]
     fn test_date_ranges() {
@@ -176,9 +362,14 @@ mod tests {
 
     fn get_dates(start_date: Date, end_date: Option<Date>) -> Vec<Date> {
         let observations = retrieve_rates(&Cli {
-            start_date,
+            start_date: Some(start_date),
             end_date,
             reverse: false,
+            roll: Roll::Backward,
+            no_cache: true,
+            dates_file: None,
+            output: output::OutputFormat::Text,
+            rrule: None,
         })
         .expect("failed to retrieve rates");
         observations.into_iter().map(|obs| obs.d).collect()
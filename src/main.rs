@@ -1,9 +1,37 @@
-use boc_usd_cad::{Cli, retrieve_rates};
+use boc_usd_cad::{Cli, batch, output, recurrence, retrieve_rates};
 use clap::Parser;
 
 fn main() {
     let args = Cli::parse();
-    for obs in retrieve_rates(&args).unwrap() {
-        println!("{}: {}", obs.d, obs.fx.v);
+
+    if let Some(dates_file) = &args.dates_file {
+        let rows = batch::resolve_batch(&args, dates_file).unwrap();
+        println!("{}", output::format_batch_rows(&rows, args.output));
+        return;
+    }
+
+    if let Some(rrule) = &args.rrule {
+        let rule = recurrence::Recurrence::parse(rrule).unwrap();
+        let start = args.start_date.expect("start_date is required with --rrule");
+        let observations = recurrence::resolve_recurrence(
+            args.reverse,
+            args.roll,
+            args.no_cache,
+            &rule,
+            start,
+            args.end_date,
+        )
+        .unwrap();
+        println!(
+            "{}",
+            output::format_observations(&observations, args.reverse, args.output)
+        );
+        return;
     }
+
+    let observations = retrieve_rates(&args).unwrap();
+    println!(
+        "{}",
+        output::format_observations(&observations, args.reverse, args.output)
+    );
 }
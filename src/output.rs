@@ -0,0 +1,210 @@
+//! Structured output formats for piping resolved rates into tax and accounting tools.
+//!
+//! Kept in the library rather than `main` so both single/range mode and batch mode serialize
+//! through the same formatters.
+
+use crate::Observation;
+use crate::batch::BatchRow;
+use jiff::civil::Date;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+/// Output format for resolved rates.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines
+    Text,
+    /// `date,rate` with a header row
+    Csv,
+    /// A single JSON array
+    Json,
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// Formats a resolved set of observations for a single currency direction.
+pub fn format_observations(observations: &[Observation], reverse: bool, format: OutputFormat) -> String {
+    let direction = direction_label(reverse);
+    match format {
+        OutputFormat::Text => observations
+            .iter()
+            .map(|obs| format!("{} {direction}: {}", obs.d, obs.fx.v))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Csv => {
+            let mut out = String::from("date,rate\n");
+            for obs in observations {
+                out.push_str(&format!("{},{}\n", obs.d, obs.fx.v));
+            }
+            out
+        }
+        OutputFormat::Json => {
+            let rows: Vec<_> = observations
+                .iter()
+                .map(|obs| ObservationRow::new(obs, direction))
+                .collect();
+            serde_json::to_string_pretty(&rows).expect("failed to serialize observations")
+        }
+        OutputFormat::Ndjson => observations
+            .iter()
+            .map(|obs| {
+                serde_json::to_string(&ObservationRow::new(obs, direction))
+                    .expect("failed to serialize observation")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Formats a resolved batch of transaction-date rows.
+pub fn format_batch_rows(rows: &[BatchRow], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => rows
+            .iter()
+            .map(|row| match row.converted_amount {
+                Some(amount) => format!(
+                    "{} ({}): {} -> {amount}",
+                    row.date, row.resolved_fixing_date, row.rate
+                ),
+                None => format!("{} ({}): {}", row.date, row.resolved_fixing_date, row.rate),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Csv => {
+            let mut out = String::from("date,resolved_fixing_date,rate,converted_amount\n");
+            for row in rows {
+                let amount = row
+                    .converted_amount
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "{},{},{},{amount}\n",
+                    row.date, row.resolved_fixing_date, row.rate
+                ));
+            }
+            out
+        }
+        OutputFormat::Json => {
+            let rows: Vec<_> = rows.iter().map(BatchRowJson::from).collect();
+            serde_json::to_string_pretty(&rows).expect("failed to serialize batch rows")
+        }
+        OutputFormat::Ndjson => rows
+            .iter()
+            .map(|row| {
+                serde_json::to_string(&BatchRowJson::from(row)).expect("failed to serialize batch row")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+        })
+    }
+}
+
+fn direction_label(reverse: bool) -> &'static str {
+    if reverse { "CAD->USD" } else { "USD->CAD" }
+}
+
+#[derive(Serialize)]
+struct ObservationRow<'a> {
+    date: Date,
+    rate: Decimal,
+    direction: &'a str,
+}
+
+impl<'a> ObservationRow<'a> {
+    fn new(obs: &Observation, direction: &'a str) -> ObservationRow<'a> {
+        ObservationRow {
+            date: obs.d,
+            rate: obs.fx.v,
+            direction,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchRowJson {
+    date: Date,
+    resolved_fixing_date: Date,
+    rate: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    converted_amount: Option<Decimal>,
+}
+
+impl From<&BatchRow> for BatchRowJson {
+    fn from(row: &BatchRow) -> BatchRowJson {
+        BatchRowJson {
+            date: row.date,
+            resolved_fixing_date: row.resolved_fixing_date,
+            rate: row.rate,
+            converted_amount: row.converted_amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    fn observation(d: Date, rate: &str) -> Observation {
+        Observation::from_rate(d, rate.parse().unwrap())
+    }
+
+    #[test]
+    fn format_observations_csv_has_header_and_row() {
+        let observations = vec![observation(date(2025, 1, 15), "1.35")];
+        let csv = format_observations(&observations, false, OutputFormat::Csv);
+        assert_eq!(csv, "date,rate\n2025-01-15,1.35\n");
+    }
+
+    #[test]
+    fn format_observations_json_round_trips_through_serde() {
+        let observations = vec![observation(date(2025, 1, 15), "1.35")];
+        let json = format_observations(&observations, true, OutputFormat::Json);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0]["date"], "2025-01-15");
+        assert_eq!(parsed[0]["rate"], "1.35");
+        assert_eq!(parsed[0]["direction"], "CAD->USD");
+    }
+
+    #[test]
+    fn format_observations_ndjson_has_one_line_per_observation() {
+        let observations = vec![observation(date(2025, 1, 15), "1.35"), observation(date(2025, 1, 16), "1.36")];
+        let ndjson = format_observations(&observations, false, OutputFormat::Ndjson);
+        assert_eq!(ndjson.lines().count(), 2);
+    }
+
+    #[test]
+    fn format_batch_rows_csv_omits_converted_amount_when_absent() {
+        let rows = vec![BatchRow {
+            date: date(2025, 1, 15),
+            resolved_fixing_date: date(2025, 1, 15),
+            rate: "1.35".parse().unwrap(),
+            converted_amount: None,
+        }];
+        let csv = format_batch_rows(&rows, OutputFormat::Csv);
+        assert_eq!(csv, "date,resolved_fixing_date,rate,converted_amount\n2025-01-15,2025-01-15,1.35,\n");
+    }
+
+    #[test]
+    fn format_batch_rows_json_skips_converted_amount_when_absent() {
+        let rows = vec![BatchRow {
+            date: date(2025, 1, 15),
+            resolved_fixing_date: date(2025, 1, 15),
+            rate: "1.35".parse().unwrap(),
+            converted_amount: None,
+        }];
+        let json = format_batch_rows(&rows, OutputFormat::Json);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed[0].get("converted_amount").is_none());
+    }
+}
@@ -0,0 +1,257 @@
+//! Expansion of an iCalendar RRULE-style recurrence expression into concrete dates.
+//!
+//! Supports the subset of RRULE needed to generate recurring-transaction fixing tables: `FREQ`,
+//! `INTERVAL`, `COUNT`/`UNTIL`, and `BYDAY`, bounded by the CLI's start/end dates. Resolution of
+//! the expanded dates to fixings reuses the same per-date roll logic as batch mode.
+
+use crate::batch::resolve_one;
+use crate::{Observation, Roll, calendar, fetch_with_cache};
+use jiff::ToSpan;
+use jiff::civil::{Date, Weekday};
+
+#[derive(Clone, Copy)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A parsed RRULE expression.
+pub struct Recurrence {
+    freq: Freq,
+    interval: i64,
+    count: Option<u32>,
+    until: Option<Date>,
+    by_day: Option<Vec<Weekday>>,
+}
+
+impl Recurrence {
+    /// Parses an RRULE-style expression, e.g. `FREQ=MONTHLY;INTERVAL=3;COUNT=4`.
+    pub fn parse(expr: &str) -> Result<Recurrence, String> {
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = None;
+
+        for part in expr.split(';') {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid RRULE part {part:?}, expected KEY=VALUE"))?;
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.trim().to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        other => return Err(format!("unsupported FREQ {other:?}")),
+                    });
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("invalid INTERVAL {value:?}: {e}"))?;
+                    if interval < 1 {
+                        return Err(format!("INTERVAL must be positive, got {interval}"));
+                    }
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|e| format!("invalid COUNT {value:?}: {e}"))?,
+                    );
+                }
+                "UNTIL" => {
+                    until = Some(
+                        value
+                            .trim()
+                            .parse::<Date>()
+                            .map_err(|e| format!("invalid UNTIL {value:?}: {e}"))?,
+                    );
+                }
+                "BYDAY" => {
+                    by_day = Some(
+                        value
+                            .split(',')
+                            .map(parse_weekday)
+                            .collect::<Result<Vec<_>, _>>()?,
+                    );
+                }
+                other => return Err(format!("unsupported RRULE field {other:?}")),
+            }
+        }
+
+        Ok(Recurrence {
+            freq: freq.ok_or_else(|| "RRULE must specify FREQ".to_string())?,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+
+    /// Expands the recurrence into concrete dates starting at `start`, bounded by `end` (if
+    /// given) and the rule's own `COUNT`/`UNTIL`.
+    pub fn expand(&self, start: Date, end: Option<Date>) -> Result<Vec<Date>, String> {
+        if self.count.is_none() && self.until.is_none() && end.is_none() {
+            return Err("recurrence must be bounded by COUNT, UNTIL, or an end date".to_string());
+        }
+
+        let mut dates = Vec::new();
+        let mut n: i64 = 0;
+        loop {
+            let cursor = self.nth_occurrence(start, n);
+            if self.until.is_some_and(|until| cursor > until) {
+                break;
+            }
+            if end.is_some_and(|end| cursor > end) {
+                break;
+            }
+            if self.count.is_some_and(|count| dates.len() as u32 >= count) {
+                break;
+            }
+            let matches_by_day = self
+                .by_day
+                .as_ref()
+                .is_none_or(|days| days.contains(&cursor.weekday()));
+            if matches_by_day && self.in_active_period(start, cursor) {
+                dates.push(cursor);
+            }
+            n += 1;
+        }
+        Ok(dates)
+    }
+
+    /// Computes the `n`th candidate date from `start`, always measuring from the original anchor
+    /// rather than the previous candidate, so a monthly recurrence anchored on a day past a short
+    /// month's end (e.g. the 31st) doesn't stay clamped once it crosses that month.
+    ///
+    /// `BYDAY` steps by day regardless of `FREQ`/`INTERVAL`: a weekly or monthly step always lands
+    /// back on the anchor's own weekday, which would make selecting multiple weekdays impossible.
+    /// `in_active_period` reapplies `INTERVAL` on top of that day-by-day walk.
+    fn nth_occurrence(&self, start: Date, n: i64) -> Date {
+        if self.by_day.is_some() {
+            return start + n.days();
+        }
+        match self.freq {
+            Freq::Daily => start + (n * self.interval).days(),
+            Freq::Weekly => start + (n * self.interval * 7).days(),
+            Freq::Monthly => start + (n * self.interval).months(),
+        }
+    }
+
+    /// Returns `true` if `cursor` falls in a `FREQ`/`INTERVAL` period that's due, relative to
+    /// `start`. Only meaningful when `BYDAY` is set, since otherwise `nth_occurrence` already
+    /// steps directly between due periods.
+    fn in_active_period(&self, start: Date, cursor: Date) -> bool {
+        self.by_day.is_none() || self.periods_since(start, cursor).rem_euclid(self.interval) == 0
+    }
+
+    /// Counts whole `FREQ` periods (days, weeks, or months) between `start` and `cursor`, using
+    /// the same unit `nth_occurrence` steps by for this rule's `FREQ`.
+    fn periods_since(&self, start: Date, cursor: Date) -> i64 {
+        match self.freq {
+            Freq::Daily => start.until(cursor).unwrap().get_days() as i64,
+            Freq::Weekly => {
+                let start_week = start - start.weekday().to_monday_zero_offset().days();
+                let cursor_week = cursor - cursor.weekday().to_monday_zero_offset().days();
+                start_week.until(cursor_week).unwrap().get_days() as i64 / 7
+            }
+            Freq::Monthly => {
+                (cursor.year() as i64 - start.year() as i64) * 12
+                    + (cursor.month() as i64 - start.month() as i64)
+            }
+        }
+    }
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Monday),
+        "TU" => Ok(Weekday::Tuesday),
+        "WE" => Ok(Weekday::Wednesday),
+        "TH" => Ok(Weekday::Thursday),
+        "FR" => Ok(Weekday::Friday),
+        "SA" => Ok(Weekday::Saturday),
+        "SU" => Ok(Weekday::Sunday),
+        other => Err(format!("unsupported BYDAY value {other:?}")),
+    }
+}
+
+/// Expands `rule` into concrete dates and resolves each to its Bank of Canada fixing.
+pub fn resolve_recurrence(
+    reverse: bool,
+    roll: Roll,
+    no_cache: bool,
+    rule: &Recurrence,
+    start: Date,
+    end: Option<Date>,
+) -> Result<Vec<Observation>, String> {
+    let dates = rule.expand(start, end)?;
+    let (Some(&min_date), Some(&max_date)) = (dates.iter().min(), dates.iter().max()) else {
+        return Ok(Vec::new());
+    };
+
+    // Pad a business day past each end so every occurrence's roll direction has somewhere to land.
+    let fetch_start = calendar::previous_business_day(min_date);
+    let fetch_end = calendar::next_business_day(max_date);
+    let observations = fetch_with_cache(reverse, no_cache, fetch_start, fetch_end)?;
+
+    dates
+        .into_iter()
+        .map(|date| resolve_one(&observations, date, roll).map(|obs| Observation::from_rate(obs.d, obs.fx.v)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    #[test]
+    fn monthly_recurrence_does_not_drift_across_short_months() {
+        let rule = Recurrence::parse("FREQ=MONTHLY;INTERVAL=1;COUNT=3").unwrap();
+        assert_eq!(
+            rule.expand(date(2026, 1, 31), None).unwrap(),
+            vec![date(2026, 1, 31), date(2026, 2, 28), date(2026, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn zero_interval_is_rejected() {
+        assert!(Recurrence::parse("FREQ=DAILY;INTERVAL=0;UNTIL=2026-12-31").is_err());
+    }
+
+    #[test]
+    fn byday_with_weekly_interval_skips_alternate_weeks() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=4").unwrap();
+        assert_eq!(
+            rule.expand(date(2026, 1, 5), None).unwrap(),
+            vec![
+                date(2026, 1, 5),
+                date(2026, 1, 7),
+                date(2026, 1, 19),
+                date(2026, 1, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn byday_with_weekly_selects_every_matching_weekday() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=6").unwrap();
+        assert_eq!(
+            rule.expand(date(2026, 1, 5), None).unwrap(),
+            vec![
+                date(2026, 1, 5),
+                date(2026, 1, 7),
+                date(2026, 1, 9),
+                date(2026, 1, 12),
+                date(2026, 1, 14),
+                date(2026, 1, 16),
+            ]
+        );
+    }
+}